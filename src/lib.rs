@@ -24,15 +24,23 @@
 //! This means for 8 elements and a data size of 16 the buffer will wrap when either 8 elements or more than 16 bytes were written.
 //! If we would insert 8 elements of 4 bytes, our buffer would thus already wrap after 4 elements.
 //!
-//! Please note that the element amount is stack allocated currently. Consequently setting a high amount of elements can lead to stack overflow.
+//! Please note that the element amount is stack allocated by default. Consequently setting a
+//! high amount of elements can lead to stack overflow. Use [`LineBuffer::with_line_capacity`]
+//! instead of [`LineBuffer::new`] to keep the book-keeping index on the heap, which is
+//! recommended for multi-million-line buffers.
 //!
+use ::std::collections::VecDeque;
 use ::std::fmt::Debug;
+use ::std::io::{self, Read, Write};
 use ::std::iter::Iterator;
 use arraydeque::{self, ArrayDeque, Wrapping};
 pub use generic_array::typenum;
 use generic_array::{ArrayLength, GenericArray};
+
+/// Size of the scratch buffer used to read chunks from a [`Read`] source.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
 /// Circular Line Buffer
-pub struct LineBuffer<T, B>
+pub struct LineBuffer<T, B = typenum::U1>
 where
     T: Debug,
     B: ArrayLength<Entry<T>>,
@@ -45,6 +53,10 @@ where
     elements: usize,
     /// total written bytes, including wrapped bytes
     written_bytes: usize,
+    /// delimiter used by the `Write` impl to cut entries at line boundaries
+    write_delim: u8,
+    /// accumulator for the not-yet-terminated line passed to `Write`
+    write_carry: Vec<u8>,
 }
 
 /// Iterator over entries in LineBuffer
@@ -57,7 +69,36 @@ pub struct Iter<'a, T: Debug> {
     first_run: bool,
     data: &'a [u8],
     len: usize,
-    iter_book: arraydeque::Iter<'a, Entry<T>>,
+    iter_book: BookKeepingIter<'a, T>,
+}
+
+/// Iterator over the raw book-keeping index, abstracting over the stack- and
+/// heap-allocated backends.
+enum BookKeepingIter<'a, T: Debug> {
+    Array(arraydeque::Iter<'a, Entry<T>>),
+    Heap(::std::collections::vec_deque::Iter<'a, Entry<T>>),
+}
+
+impl<'a, T: Debug> Iterator for BookKeepingIter<'a, T> {
+    type Item = &'a Entry<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            BookKeepingIter::Array(iter) => iter.next(),
+            BookKeepingIter::Heap(iter) => iter.next(),
+        }
+    }
+}
+
+impl<'a, T: Debug> DoubleEndedIterator for BookKeepingIter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            BookKeepingIter::Array(iter) => iter.next_back(),
+            BookKeepingIter::Heap(iter) => iter.next_back(),
+        }
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T>
@@ -103,15 +144,48 @@ where
     }
 }
 
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+where
+    T: Debug,
+{
+    /// Walks entries newest-to-oldest. Once an entry fails the validity window check
+    /// (i.e. it has already wrapped out), every remaining entry in that direction is
+    /// invalid too, since the window is a contiguous suffix of insertion order - so
+    /// iteration ends there.
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = self.iter_book.next_back()?;
+        if self.written_bytes >= self.capacity && entry.start < self.written_bytes - self.capacity
+        {
+            return None;
+        }
+        let start = entry.start % self.capacity;
+        Some((&self.data[start..start + entry.length], &entry.addition))
+    }
+}
+
 /// Simple book keeping index
 ///
 /// Doesn't handle validation
-struct BookKeeping<T, B>
+///
+/// Backed either by a fixed-size [`GenericArray`] ring (the default, sized at
+/// compile time via `B`, built on the stack and then boxed) or, when created through [`LineBuffer::with_line_capacity`],
+/// by a heap-allocated ring sized at runtime. Both backends share the exact same
+/// "floating window" index math, so callers see identical behavior either way.
+///
+/// The `Array` variant is boxed so that `size_of::<BookKeeping<T, B>>()` (and thus
+/// `size_of::<LineBuffer<T, B>>()`) stays small and independent of `B` - otherwise a
+/// `LineBuffer` built via `with_line_capacity` with a large `B` left over from an
+/// unrelated array-backed instantiation would still carry the full stack-sized
+/// `GenericArray` inline, reintroducing the exact stack overflow risk this backend
+/// exists to avoid.
+enum BookKeeping<T, B>
 where
     T: Debug,
     B: ArrayLength<Entry<T>>,
 {
-    index: ArrayDeque<GenericArray<Entry<T>, B>, Wrapping>,
+    Array(Box<ArrayDeque<GenericArray<Entry<T>, B>, Wrapping>>),
+    Heap(HeapBookKeeping<T>),
 }
 
 impl<T, B> BookKeeping<T, B>
@@ -120,14 +194,23 @@ where
     B: ArrayLength<Entry<T>>,
 {
     fn new() -> Self {
-        Self {
-            index: ArrayDeque::new(),
-        }
+        BookKeeping::Array(Box::new(ArrayDeque::new()))
+    }
+
+    fn new_heap(lines: usize) -> Self {
+        BookKeeping::Heap(HeapBookKeeping::new(lines))
     }
 
     #[cfg(test)]
     pub fn print_index(&self) {
-        dbg!(&self.index);
+        match self {
+            BookKeeping::Array(index) => {
+                dbg!(index);
+            }
+            BookKeeping::Heap(index) => {
+                dbg!(&index.entries);
+            }
+        }
     }
 
     /// Upper bound amount of items
@@ -135,39 +218,84 @@ where
     /// Real value varies depending on amount of valid entries
     #[inline]
     fn length_max(&self) -> usize {
-        self.index.len()
+        match self {
+            BookKeeping::Array(index) => index.len(),
+            BookKeeping::Heap(index) => index.entries.len(),
+        }
     }
 
     #[inline]
-    fn iter(&self) -> arraydeque::Iter<Entry<T>> {
-        self.index.iter()
+    fn iter(&self) -> BookKeepingIter<T> {
+        match self {
+            BookKeeping::Array(index) => BookKeepingIter::Array(index.iter()),
+            BookKeeping::Heap(index) => BookKeepingIter::Heap(index.entries.iter()),
+        }
     }
 
     /// Capacity of elements that can be hold.
     #[inline]
     pub fn capacity(&self) -> usize {
-        self.index.capacity()
+        match self {
+            BookKeeping::Array(index) => index.capacity(),
+            BookKeeping::Heap(index) => index.capacity,
+        }
     }
 
     #[inline]
     fn append(&mut self, addition: T, start: usize, length: usize) {
-        self.index.push_back(Entry {
+        let entry = Entry {
             start,
             length,
             addition,
-        });
+        };
+        match self {
+            BookKeeping::Array(index) => {
+                index.push_back(entry);
+            }
+            BookKeeping::Heap(index) => {
+                if index.entries.len() == index.capacity {
+                    index.entries.pop_front();
+                }
+                index.entries.push_back(entry);
+            }
+        }
     }
 
     #[inline]
     fn get(&self, idx: usize, current_max: usize) -> Option<&Entry<T>> {
         // calculate total position based on "floating window" of elements in buffer
-        let min = if current_max < self.index.capacity() {
-            0 // no wrap till now
-        } else {
-            current_max - self.index.capacity()
-        };
+        // (0 while current_max < capacity, i.e. no wrap till now)
+        let min = current_max.saturating_sub(self.capacity());
         let pos = if idx >= min { idx - min } else { idx };
-        self.index.get(pos)
+        match self {
+            BookKeeping::Array(index) => index.get(pos),
+            BookKeeping::Heap(index) => index.entries.get(pos),
+        }
+    }
+}
+
+/// Heap-allocated book-keeping ring with a runtime-chosen line capacity.
+///
+/// Manually implements the same overwrite-oldest ("Wrapping") semantics `arraydeque`
+/// applies to the `GenericArray` backend, so a full ring drops its oldest entry on
+/// `append` instead of growing unbounded.
+struct HeapBookKeeping<T>
+where
+    T: Debug,
+{
+    entries: VecDeque<Entry<T>>,
+    capacity: usize,
+}
+
+impl<T> HeapBookKeeping<T>
+where
+    T: Debug,
+{
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
     }
 }
 
@@ -198,6 +326,28 @@ where
             tail: 0,
             book_keeping: BookKeeping::new(),
             written_bytes: 0,
+            write_delim: b'\n',
+            write_carry: Vec::new(),
+        }
+    }
+
+    /// Create a new circular buffer whose line book-keeping lives on the heap instead
+    /// of being stack-allocated via `B`.
+    ///
+    /// `lines` is the maximum amount of entries held at once, `bytes` the data cache
+    /// size in bytes, exactly like [`new`](LineBuffer::new). Unlike `new`, the line
+    /// capacity is chosen at runtime, so a large capacity no longer risks a stack
+    /// overflow; pick this constructor when `lines` is large or only known at runtime.
+    /// `B` is irrelevant in this mode and can be left at its default.
+    pub fn with_line_capacity(lines: usize, bytes: usize) -> Self {
+        Self {
+            data: vec![0; bytes],
+            elements: 0,
+            tail: 0,
+            book_keeping: BookKeeping::new_heap(lines),
+            written_bytes: 0,
+            write_delim: b'\n',
+            write_carry: Vec::new(),
         }
     }
 
@@ -223,6 +373,15 @@ where
         }
     }
 
+    /// Returns the last `n` valid entries, newest first.
+    ///
+    /// Cheap way for a log viewer to render the most recent lines without scanning
+    /// the whole ring via [`iter`](LineBuffer::iter).
+    #[inline]
+    pub fn tail(&self, n: usize) -> impl Iterator<Item = (&[u8], &T)> {
+        self.iter().rev().take(n)
+    }
+
     /// Total amount of inserted elements
     pub fn elements(&self) -> usize {
         self.elements
@@ -243,6 +402,12 @@ where
         self.data.len()
     }
 
+    /// Set the delimiter used by the `Write` implementation to cut entries at line
+    /// boundaries. Defaults to `b'\n'`.
+    pub fn set_write_delim(&mut self, delim: u8) {
+        self.write_delim = delim;
+    }
+
     /// Get element at index, idx counting up since first element inserted.
     pub fn get(&self, idx: usize) -> Option<(&[u8], &T)> {
         // idx > seen lines
@@ -292,6 +457,204 @@ where
     }
 }
 
+impl<B> LineBuffer<(), B>
+where
+    B: ArrayLength<Entry<()>>,
+{
+    /// Fill the buffer from a [`Read`] source, splitting the incoming bytes on `delim`.
+    ///
+    /// Reads the source in fixed-size chunks and inserts one entry per `delim`-terminated
+    /// line found. Lines split across two reads are reassembled via a small carry buffer,
+    /// the same way [`std::io::BufReader`] handles short reads. On EOF (`read` returning
+    /// `Ok(0)`) any trailing bytes without a final delimiter are flushed as a last entry.
+    ///
+    /// Returns the number of entries inserted.
+    pub fn fill_from_reader<R: Read>(&mut self, reader: &mut R, delim: u8) -> io::Result<usize> {
+        let mut chunk = [0u8; READ_CHUNK_SIZE];
+        let mut carry: Vec<u8> = Vec::new();
+        let mut inserted = 0;
+        loop {
+            let n = reader.read(&mut chunk)?;
+            if n == 0 {
+                if !carry.is_empty() {
+                    self.insert(&carry, ());
+                    inserted += 1;
+                }
+                break;
+            }
+            let mut start = 0;
+            for i in 0..n {
+                if chunk[i] == delim {
+                    if carry.is_empty() {
+                        self.insert(&chunk[start..i], ());
+                    } else {
+                        carry.extend_from_slice(&chunk[start..i]);
+                        self.insert(&carry, ());
+                        carry.clear();
+                    }
+                    inserted += 1;
+                    start = i + 1;
+                }
+            }
+            if start < n {
+                carry.extend_from_slice(&chunk[start..n]);
+            }
+        }
+        Ok(inserted)
+    }
+}
+
+/// Line-buffered `Write` sink, mirroring [`std::io::LineWriter`].
+///
+/// Bytes passed to `write` are cut into entries on [`set_write_delim`](LineBuffer::set_write_delim)
+/// (`b'\n'` by default); a trailing, not yet delimiter-terminated chunk is held back and
+/// only turned into an entry once it is completed, or on an explicit `flush`.
+impl<B> Write for LineBuffer<(), B>
+where
+    B: ArrayLength<Entry<()>>,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let delim = self.write_delim;
+        let mut start = 0;
+        for i in 0..buf.len() {
+            if buf[i] == delim {
+                if self.write_carry.is_empty() {
+                    self.insert(&buf[start..i], ());
+                } else {
+                    self.write_carry.extend_from_slice(&buf[start..i]);
+                    let line = ::std::mem::take(&mut self.write_carry);
+                    self.insert(&line, ());
+                }
+                start = i + 1;
+            }
+        }
+        if start < buf.len() {
+            self.write_carry.extend_from_slice(&buf[start..]);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.write_carry.is_empty() {
+            let line = ::std::mem::take(&mut self.write_carry);
+            self.insert(&line, ());
+        }
+        Ok(())
+    }
+}
+
+/// Magic bytes identifying a [`LineBuffer`] snapshot stream.
+const SNAPSHOT_MAGIC: [u8; 4] = *b"LBUF";
+/// Snapshot stream format version, bump on incompatible format changes.
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// Write `value` as a LEB128 varint: 7 bits per byte, low bits first, with the high
+/// bit set on every byte but the last.
+fn write_varint<W: Write>(w: &mut W, mut value: u64) -> io::Result<()> {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value != 0 {
+            byte |= 0x80;
+        }
+        w.write_all(&[byte])?;
+        if value == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Read a LEB128 varint written by [`write_varint`].
+fn read_varint<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        r.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+impl<T, B> LineBuffer<T, B>
+where
+    T: Debug + Default,
+    B: ArrayLength<Entry<T>>,
+{
+    /// Persist the currently valid entries to `w` so they can be restored later via
+    /// [`read_snapshot`](LineBuffer::read_snapshot), without replaying every `insert`.
+    ///
+    /// The stream is a header (magic, format version, byte capacity, line capacity,
+    /// entry count, total written bytes) followed by each live entry as a LEB128
+    /// varint length prefix and its raw line bytes. Only entries still within the
+    /// live window are written; entries that have already wrapped out are skipped,
+    /// matching what [`iter`](LineBuffer::iter) would yield.
+    ///
+    /// The `addition` value of each entry is not part of the stream and is restored
+    /// as `T::default()`.
+    pub fn write_snapshot<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let lines: Vec<&[u8]> = self.iter().map(|(data, _)| data).collect();
+
+        w.write_all(&SNAPSHOT_MAGIC)?;
+        w.write_all(&[SNAPSHOT_VERSION])?;
+        write_varint(w, self.capacity_bytes() as u64)?;
+        write_varint(w, self.capacity() as u64)?;
+        write_varint(w, lines.len() as u64)?;
+        write_varint(w, self.written_bytes as u64)?;
+
+        for line in lines {
+            write_varint(w, line.len() as u64)?;
+            w.write_all(line)?;
+        }
+        Ok(())
+    }
+
+    /// Restore a buffer previously persisted via
+    /// [`write_snapshot`](LineBuffer::write_snapshot).
+    ///
+    /// Re-inserts each serialized entry in order, which naturally rebuilds the
+    /// `start`/`length` offsets and wrap state. The restored buffer is always
+    /// constructed via [`with_line_capacity`](LineBuffer::with_line_capacity) using
+    /// the line capacity stored in the header, rather than `B`, so a snapshot of a
+    /// buffer with a large line capacity doesn't get silently truncated to whatever
+    /// `B` happens to default to.
+    pub fn read_snapshot<R: Read>(r: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if magic != SNAPSHOT_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a LineBuffer snapshot",
+            ));
+        }
+        let mut version = [0u8; 1];
+        r.read_exact(&mut version)?;
+        if version[0] != SNAPSHOT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unsupported LineBuffer snapshot version",
+            ));
+        }
+
+        let capacity_bytes = read_varint(r)? as usize;
+        let line_capacity = read_varint(r)? as usize;
+        let elements = read_varint(r)?;
+        let _written_bytes = read_varint(r)?;
+
+        let mut buffer = Self::with_line_capacity(line_capacity, capacity_bytes);
+        for _ in 0..elements {
+            let len = read_varint(r)? as usize;
+            let mut line = vec![0u8; len];
+            r.read_exact(&mut line)?;
+            buffer.insert(&line, T::default());
+        }
+        Ok(buffer)
+    }
+}
+
 #[test]
 fn insert_simple() {
     let mut buffer: LineBuffer<i32, typenum::U8> = LineBuffer::new(8);
@@ -445,3 +808,147 @@ fn iter_test_wrap() {
     }
     assert_eq!(i, 16);
 }
+
+#[test]
+fn write_impl_splits_on_newline() {
+    use ::std::io::Write;
+
+    let mut buffer: LineBuffer<(), typenum::U8> = LineBuffer::new(64);
+    write!(buffer, "foo\nbar\nba").unwrap();
+    assert_eq!(buffer.get(0), Some((b"foo".as_ref(), &())));
+    assert_eq!(buffer.get(1), Some((b"bar".as_ref(), &())));
+    assert_eq!(buffer.get(2), None);
+
+    buffer.write_all(b"z\n").unwrap();
+    assert_eq!(buffer.get(2), Some((b"baz".as_ref(), &())));
+
+    write!(buffer, "trailing").unwrap();
+    assert_eq!(buffer.get(3), None);
+    buffer.flush().unwrap();
+    assert_eq!(buffer.get(3), Some((b"trailing".as_ref(), &())));
+}
+
+#[test]
+fn heap_book_keeping_matches_array() {
+    let mut buffer: LineBuffer<i32> = LineBuffer::with_line_capacity(8, 9);
+    for i in 0..12 {
+        buffer.insert(format!("{}", i).as_bytes(), i);
+    }
+    assert_eq!(buffer.capacity(), 8);
+    for i in 0..5 {
+        assert_eq!(buffer.get(i), None);
+    }
+    for i in 5..12 {
+        assert_eq!(
+            buffer.get(i),
+            Some((format!("{}", i).as_bytes(), &(i as i32)))
+        );
+    }
+
+    let mut i = 5;
+    for (data, flag) in buffer.iter() {
+        assert_eq!(*flag, i);
+        assert_eq!(data, format!("{}", i).as_bytes());
+        i += 1;
+    }
+    assert_eq!(i, 12);
+}
+
+#[test]
+fn snapshot_round_trip() {
+    let mut buffer: LineBuffer<(), typenum::U8> = LineBuffer::new(9);
+    for i in 0..12 {
+        buffer.insert(format!("{}", i).as_bytes(), ());
+    }
+
+    let mut bytes = Vec::new();
+    buffer.write_snapshot(&mut bytes).unwrap();
+
+    let mut cursor = ::std::io::Cursor::new(bytes);
+    let restored: LineBuffer<(), typenum::U8> = LineBuffer::read_snapshot(&mut cursor).unwrap();
+
+    let original: Vec<Vec<u8>> = buffer.iter().map(|(data, _)| data.to_vec()).collect();
+    let restored_lines: Vec<Vec<u8>> = restored.iter().map(|(data, _)| data.to_vec()).collect();
+    assert_eq!(original, restored_lines);
+}
+
+#[test]
+fn snapshot_round_trip_preserves_line_capacity() {
+    let mut buffer: LineBuffer<(), typenum::U1> = LineBuffer::with_line_capacity(5, 200);
+    for i in 0..5 {
+        buffer.insert(format!("{}", i).as_bytes(), ());
+    }
+
+    let mut bytes = Vec::new();
+    buffer.write_snapshot(&mut bytes).unwrap();
+
+    let mut cursor = ::std::io::Cursor::new(bytes);
+    let restored: LineBuffer<(), typenum::U1> = LineBuffer::read_snapshot(&mut cursor).unwrap();
+
+    assert_eq!(restored.capacity(), 5);
+    assert_eq!(restored.iter().count(), 5);
+}
+
+#[test]
+fn iter_rev_wrap() {
+    let mut buffer: LineBuffer<i32, typenum::U8> = LineBuffer::new(9);
+    for i in 0..16 {
+        buffer.insert(format!("{}", i).as_bytes(), i);
+    }
+    let mut i: i32 = 15;
+    for (data, flag) in buffer.iter().rev() {
+        assert_eq!(*flag, i);
+        assert_eq!(data, format!("{}", i).as_bytes());
+        i -= 1;
+    }
+    assert_eq!(i, 11);
+}
+
+#[test]
+fn tail_yields_newest_first() {
+    let mut buffer: LineBuffer<i32, typenum::U8> = LineBuffer::new(9);
+    for i in 0..16 {
+        buffer.insert(format!("{}", i).as_bytes(), i);
+    }
+    let tail: Vec<i32> = buffer.tail(2).map(|(_, flag)| *flag).collect();
+    assert_eq!(tail, vec![15, 14]);
+
+    let tail_all: Vec<i32> = buffer.tail(100).map(|(_, flag)| *flag).collect();
+    assert_eq!(tail_all, vec![15, 14, 13, 12]);
+}
+
+/// Hands back only a few bytes per `read` call, regardless of the requested buffer
+/// size, to exercise `fill_from_reader`'s carry logic for lines split across reads.
+struct ShortReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    step: usize,
+}
+
+impl<'a> Read for ShortReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = (self.data.len() - self.pos).min(self.step).min(buf.len());
+        buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn fill_from_reader_handles_short_reads_and_eof_carry() {
+    let mut buffer: LineBuffer<(), typenum::U8> = LineBuffer::new(64);
+    let data = b"foo\nbar\nbaz\ntrailing";
+    let mut reader = ShortReader {
+        data,
+        pos: 0,
+        step: 3,
+    };
+
+    let inserted = buffer.fill_from_reader(&mut reader, b'\n').unwrap();
+
+    assert_eq!(inserted, 4);
+    assert_eq!(buffer.get(0), Some((b"foo".as_ref(), &())));
+    assert_eq!(buffer.get(1), Some((b"bar".as_ref(), &())));
+    assert_eq!(buffer.get(2), Some((b"baz".as_ref(), &())));
+    assert_eq!(buffer.get(3), Some((b"trailing".as_ref(), &())));
+}
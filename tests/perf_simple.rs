@@ -1,5 +1,6 @@
 use linebuffer::{typenum, LineBuffer};
 use std::convert::TryInto;
+use std::io::Cursor;
 use std::time::*;
 
 #[test]
@@ -30,5 +31,25 @@ fn perf_simple() {
 #[test]
 #[ignore]
 fn perf_from_file() {
-    unimplemented!();
+    const AMOUNT: usize = 512_000;
+    let mut buffer: LineBuffer<(), typenum::U2048> = LineBuffer::new(AMOUNT);
+    let max: u32 = 1_000_000;
+    let mut data = Vec::new();
+    for i in 0..max {
+        // decimal encoding, unlike the raw bytes, can never contain the `\n` delimiter
+        data.extend_from_slice(format!("{}", i).as_bytes());
+        data.push(b'\n');
+    }
+    let mut reader = Cursor::new(data);
+    let start = Instant::now();
+    let inserted = buffer.fill_from_reader(&mut reader, b'\n').unwrap();
+    let nanos = start.elapsed().as_nanos();
+    println!("Duration: {} ns for {} entries", nanos, inserted);
+    assert_eq!(inserted, max as usize);
+
+    let expected: u32 = max - 1;
+    assert_eq!(
+        buffer.get((max - 1) as usize),
+        Some((format!("{}", expected).as_bytes(), &()))
+    );
 }